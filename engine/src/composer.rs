@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::preprocessor::{replace_word, Defines};
+use crate::{error_output, finish_conversion, parse_glsl, parse_stage, parse_wgsl, BoundsCheckConfig, ConversionOutput, SpvOptions};
+
+struct ComposerModule {
+    source: String,
+    lang: String,
+}
+
+/// Multi-module composition, modeled on naga_oil's `Composer`: modules are registered by name
+/// and spliced into an entry source wherever it has a `#import "name"` (or `#import name::symbol`)
+/// directive, with each imported module's top-level symbols mangled to avoid collisions.
+#[wasm_bindgen]
+pub struct ShaderComposer {
+    modules: HashMap<String, ComposerModule>,
+}
+
+#[wasm_bindgen]
+impl ShaderComposer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ShaderComposer {
+        ShaderComposer { modules: HashMap::new() }
+    }
+
+    /// Registers a module by name so later `#import "name"` directives can resolve it
+    pub fn add_module(&mut self, name: &str, source: &str, lang: &str) {
+        self.modules.insert(name.to_string(), ComposerModule {
+            source: source.to_string(),
+            lang: lang.to_string(),
+        });
+    }
+
+    /// Resolves `entry_source`'s imports against registered modules and runs the spliced
+    /// result through the normal parse/validate/write pipeline
+    pub fn convert_composed(&self, entry_source: &str, source_lang: &str, target_lang: &str, stage_str: &str) -> ConversionOutput {
+        let stage = parse_stage(stage_str);
+
+        let resolved = match self.resolve(entry_source, "<entry>", source_lang, &mut Vec::new()) {
+            Ok(code) => code,
+            Err(e) => return error_output(&e),
+        };
+
+        let module = match source_lang {
+            "wgsl" => parse_wgsl(&resolved, &Defines::new()),
+            "glsl" | _ => parse_glsl(&resolved, stage, &Defines::new()),
+        };
+
+        finish_conversion(module, target_lang, stage, BoundsCheckConfig::default(), "", SpvOptions::default())
+    }
+
+    /// Recursively inlines `#import` directives. `seen` tracks modules already spliced into this
+    /// chain, both to avoid duplicate definitions and to catch import cycles -- but only gates the
+    /// splicing itself: a module can be `#import`ed by more than one sibling in a diamond (`b` and
+    /// `c` both importing `d`), and each importer still needs its own call sites to that module's
+    /// symbols rewritten to the mangled name, even on the second (or later) `#import` of it.
+    fn resolve(&self, source: &str, origin: &str, expected_lang: &str, seen: &mut Vec<String>) -> Result<String, String> {
+        let mut spliced = String::new();
+        let mut remainder = String::new();
+        let mut renames: Vec<(String, String)> = Vec::new();
+
+        for (line_no, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed.strip_prefix("#import") {
+                let import = rest.trim().trim_matches('"');
+                let module_name = import.split("::").next().unwrap_or(import).trim();
+
+                let module = self.modules.get(module_name).ok_or_else(|| {
+                    format!("{}:{}: unknown module `{}`", origin, line_no + 1, module_name)
+                })?;
+                if module.lang != expected_lang {
+                    return Err(format!(
+                        "{}:{}: module `{}` is `{}`, expected `{}`",
+                        origin, line_no + 1, module_name, module.lang, expected_lang
+                    ));
+                }
+
+                for name in declared_names(&module.source) {
+                    renames.push((name.clone(), format!("_{}_{}", module_name, name)));
+                }
+
+                if !seen.iter().any(|m| m == module_name) {
+                    seen.push(module_name.to_string());
+                    let nested = self.resolve(&module.source, module_name, expected_lang, seen)?;
+                    spliced.push_str(&mangle_symbols(&nested, &module.source, module_name));
+                    spliced.push('\n');
+                }
+                continue;
+            }
+            remainder.push_str(line);
+            remainder.push('\n');
+        }
+
+        for (name, mangled) in &renames {
+            remainder = replace_word(&remainder, name, mangled);
+        }
+
+        Ok(format!("{}{}", spliced, remainder))
+    }
+}
+
+impl Default for ShaderComposer {
+    fn default() -> Self {
+        ShaderComposer::new()
+    }
+}
+
+/// Renames every `fn`/`struct`/`var`/`const`/`alias` declared in the module's own
+/// `module_source`, and every reference to it in `source` (the module with its nested imports
+/// already spliced in), with a `_<module>_` prefix so two imported modules can each define e.g. a
+/// `saturate` helper without colliding in the spliced module. Names from nested imports keep the
+/// mangled names their own module gave them. A single leading underscore, not a double one:
+/// WGSL reserves identifiers starting with `__` and naga's frontend hard-rejects them.
+fn mangle_symbols(source: &str, module_source: &str, module_name: &str) -> String {
+    let prefix = format!("_{}_", module_name);
+    let mut result = source.to_string();
+    for name in declared_names(module_source) {
+        result = replace_word(&result, &name, &format!("{}{}", prefix, name));
+    }
+    result
+}
+
+fn declared_names(source: &str) -> Vec<String> {
+    const KEYWORDS: [&str; 4] = ["fn ", "struct ", "const ", "alias "];
+    let mut names = Vec::new();
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        for kw in KEYWORDS {
+            if let Some(name) = take_ident(trimmed.strip_prefix(kw)) {
+                names.push(name);
+            }
+        }
+
+        // `var` declarations take an optional `<address space[, access mode]>` template before
+        // the name, e.g. `var<uniform> globals: ...;` or plain `var counter: u32;` -- a bare
+        // "var " prefix check misses the (far more common) templated form.
+        if let Some(rest) = trimmed.strip_prefix("var") {
+            if let Some(rest) = rest.strip_prefix('<') {
+                if let Some((_, after_template)) = rest.split_once('>') {
+                    if let Some(name) = take_ident(Some(after_template)) {
+                        names.push(name);
+                    }
+                }
+            } else if let Some(name) = take_ident(rest.strip_prefix(' ')) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+fn take_ident(rest: Option<&str>) -> Option<String> {
+    let name: String = rest?.trim_start().chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    if name.is_empty() { None } else { Some(name) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_composed_resolves_import() {
+        let mut composer = ShaderComposer::new();
+        composer.add_module(
+            "math",
+            "fn square(x: f32) -> f32 { return x * x; }",
+            "wgsl",
+        );
+
+        let entry = "#import \"math\"\n@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(square(2.0)); }";
+        let result = composer.convert_composed(entry, "wgsl", "wgsl", "fragment");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_composed_mangles_templated_var_collision() {
+        let mut composer = ShaderComposer::new();
+        composer.add_module(
+            "fog",
+            "@group(0) @binding(0)\nvar<uniform> density: f32;\nfn fog_factor(d: f32) -> f32 { return density * d; }",
+            "wgsl",
+        );
+        composer.add_module(
+            "haze",
+            "@group(0) @binding(1)\nvar<uniform> density: f32;\nfn haze_factor(d: f32) -> f32 { return density * d; }",
+            "wgsl",
+        );
+
+        let entry = "#import \"fog\"\n#import \"haze\"\n@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(fog_factor(1.0) + haze_factor(1.0)); }";
+        let result = composer.convert_composed(entry, "wgsl", "wgsl", "fragment");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_composed_diamond_import_rewrites_both_importers() {
+        let mut composer = ShaderComposer::new();
+        composer.add_module("d", "fn shared_helper(x: f32) -> f32 { return x + 1.0; }", "wgsl");
+        composer.add_module(
+            "b",
+            "#import \"d\"\nfn b_fn(x: f32) -> f32 { return shared_helper(x) * 2.0; }",
+            "wgsl",
+        );
+        composer.add_module(
+            "c",
+            "#import \"d\"\nfn c_fn(x: f32) -> f32 { return shared_helper(x) * 3.0; }",
+            "wgsl",
+        );
+
+        let entry = "#import \"b\"\n#import \"c\"\n@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(b_fn(1.0) + c_fn(1.0)); }";
+        let result = composer.convert_composed(entry, "wgsl", "wgsl", "fragment");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_composed_unknown_module_errors() {
+        let composer = ShaderComposer::new();
+        let entry = "#import \"missing\"\n@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let result = composer.convert_composed(entry, "wgsl", "wgsl", "fragment");
+        assert!(!result.success);
+    }
+}
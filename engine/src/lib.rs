@@ -1,6 +1,12 @@
 use wasm_bindgen::prelude::*;
 use naga::{front, back, valid};
 
+mod preprocessor;
+use preprocessor::Defines;
+
+mod composer;
+pub use composer::ShaderComposer;
+
 #[wasm_bindgen]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
@@ -10,6 +16,7 @@ pub fn init_panic_hook() {
 pub struct ConversionOutput {
     success: bool,
     output: String,
+    output_bytes: Vec<u8>,
     error: String,
 }
 
@@ -17,32 +24,405 @@ pub struct ConversionOutput {
 impl ConversionOutput {
     #[wasm_bindgen(getter)]
     pub fn success(&self) -> bool { self.success }
-    
+
     #[wasm_bindgen(getter)]
     pub fn output(&self) -> String { self.output.clone() }
-    
+
+    /// Binary payload for targets that don't produce text (e.g. "spv")
+    #[wasm_bindgen(getter)]
+    pub fn output_binary(&self) -> Vec<u8> { self.output_bytes.clone() }
+
     #[wasm_bindgen(getter)]
     pub fn error(&self) -> String { self.error.clone() }
 }
 
+/// Result of a successful write pass: either textual source or a binary module
+enum ConversionPayload {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Mirrors `naga::proc::BoundsCheckPolicy`, exposed to JS since naga's own type isn't `#[wasm_bindgen]`
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum BoundsCheckPolicy {
+    Restrict,
+    ReadZeroSkipWrite,
+    Unchecked,
+}
+
+impl From<BoundsCheckPolicy> for naga::proc::BoundsCheckPolicy {
+    fn from(policy: BoundsCheckPolicy) -> Self {
+        match policy {
+            BoundsCheckPolicy::Restrict => naga::proc::BoundsCheckPolicy::Restrict,
+            BoundsCheckPolicy::ReadZeroSkipWrite => naga::proc::BoundsCheckPolicy::ReadZeroSkipWrite,
+            BoundsCheckPolicy::Unchecked => naga::proc::BoundsCheckPolicy::Unchecked,
+        }
+    }
+}
+
+/// Per-target bounds-check configuration, matching naga-cli's
+/// `index-bounds-check-policy` / `buffer-bounds-check-policy` / `texture-bounds-check-policy` flags
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct BoundsCheckConfig {
+    index: BoundsCheckPolicy,
+    buffer: BoundsCheckPolicy,
+    texture: BoundsCheckPolicy,
+}
+
+#[wasm_bindgen]
+impl BoundsCheckConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(index: BoundsCheckPolicy, buffer: BoundsCheckPolicy, texture: BoundsCheckPolicy) -> Self {
+        BoundsCheckConfig { index, buffer, texture }
+    }
+}
+
+impl Default for BoundsCheckConfig {
+    fn default() -> Self {
+        BoundsCheckConfig {
+            index: BoundsCheckPolicy::Unchecked,
+            buffer: BoundsCheckPolicy::Unchecked,
+            texture: BoundsCheckPolicy::Unchecked,
+        }
+    }
+}
+
+impl BoundsCheckConfig {
+    fn to_naga_policies(self) -> naga::proc::BoundsCheckPolicies {
+        naga::proc::BoundsCheckPolicies {
+            index: self.index.into(),
+            buffer: self.buffer.into(),
+            image_load: self.texture.into(),
+            image_store: self.texture.into(),
+            binding_array: self.index.into(),
+        }
+    }
+}
+
+/// SPIR-V output configuration, matching naga-cli's `--spv-version` and `--spv-capability` flags.
+/// Mirrors `BoundsCheckConfig`'s shape: a `#[wasm_bindgen(constructor)]` struct for the JS-facing
+/// fields, with the naga-type conversions kept in a plain `impl` block.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SpvOptions {
+    version_major: u8,
+    version_minor: u8,
+    /// JSON array of `back::spv::Capability` variant names (e.g. `["Shader", "Float64"]`), or ""
+    /// to leave naga's capability set unrestricted. Only the names listed in
+    /// `parse_spv_capability` are recognized; any other name is an error.
+    capabilities_json: String,
+}
+
+#[wasm_bindgen]
+impl SpvOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(version_major: u8, version_minor: u8, capabilities_json: &str) -> Self {
+        SpvOptions { version_major, version_minor, capabilities_json: capabilities_json.to_string() }
+    }
+}
+
+impl Default for SpvOptions {
+    fn default() -> Self {
+        SpvOptions { version_major: 1, version_minor: 3, capabilities_json: String::new() }
+    }
+}
+
+impl SpvOptions {
+    fn lang_version(&self) -> (u8, u8) {
+        (self.version_major, self.version_minor)
+    }
+
+    fn capabilities(&self) -> Result<Option<naga::FastHashSet<back::spv::Capability>>, String> {
+        if self.capabilities_json.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let names: Vec<String> = serde_json::from_str(&self.capabilities_json)
+            .map_err(|e| format!("Invalid capabilities JSON: {:?}", e))?;
+        let mut capabilities = naga::FastHashSet::default();
+        for name in names {
+            capabilities.insert(parse_spv_capability(&name)?);
+        }
+        Ok(Some(capabilities))
+    }
+}
+
+fn parse_spv_capability(name: &str) -> Result<back::spv::Capability, String> {
+    use back::spv::Capability;
+    Ok(match name {
+        "Matrix" => Capability::Matrix,
+        "Shader" => Capability::Shader,
+        "Geometry" => Capability::Geometry,
+        "Tessellation" => Capability::Tessellation,
+        "Float64" => Capability::Float64,
+        "Int64" => Capability::Int64,
+        "Int16" => Capability::Int16,
+        "ImageQuery" => Capability::ImageQuery,
+        "DerivativeControl" => Capability::DerivativeControl,
+        "SampleRateShading" => Capability::SampleRateShading,
+        "MultiViewport" => Capability::MultiViewport,
+        "StorageImageExtendedFormats" => Capability::StorageImageExtendedFormats,
+        "MultiView" => Capability::MultiView,
+        other => return Err(format!("Unknown SPIR-V capability: {}", other)),
+    })
+}
+
 /// Main conversion function supporting multiple source and target languages
-/// source_lang: "glsl" | "wgsl"
-/// target_lang: "glsl" | "hlsl" | "wgsl" | "msl"
+/// source_lang: "glsl" | "wgsl" | "spv"
+/// target_lang: "glsl" | "hlsl" | "wgsl" | "msl" | "spv"
 /// stage_str: "vertex" | "fragment" | "compute"
+/// defines_json: JSON object of `{name: bool|integer|string}` preprocessor defines, or "" for none
+/// entry_point: name of the entry point to target, or "" to default to "main". Only honored by
+/// backends that select a single entry point (glsl); hlsl and msl emit every entry point into
+/// one text blob regardless, so entry_point is ignored for those targets.
+///
+/// Note: "spv" as a source_lang is rejected here since SPIR-V is binary and can't
+/// travel through a `&str`; use `convert_shader_binary` instead. Bounds-check
+/// policies default to naga's `Unchecked`; use `convert_shader_opts` to set them.
 #[wasm_bindgen]
-pub fn convert_shader(code: &str, source_lang: &str, target_lang: &str, stage_str: &str) -> ConversionOutput {
-    let stage = match stage_str {
-        "vertex" => naga::ShaderStage::Vertex,
-        "compute" => naga::ShaderStage::Compute,
-        _ => naga::ShaderStage::Fragment,
+pub fn convert_shader(code: &str, source_lang: &str, target_lang: &str, stage_str: &str, defines_json: &str, entry_point: &str) -> ConversionOutput {
+    let stage = parse_stage(stage_str);
+    let defines = match preprocessor::parse_defines(defines_json) {
+        Ok(d) => d,
+        Err(e) => return error_output(&e),
     };
 
     // Parse source code into Naga IR Module
     let module = match source_lang {
-        "wgsl" => parse_wgsl(code),
-        "glsl" | _ => parse_glsl(code, stage),
+        "wgsl" => parse_wgsl(code, &defines),
+        "spv" => Err("SPIR-V source is binary; use convert_shader_binary instead".to_string()),
+        "glsl" | _ => parse_glsl(code, stage, &defines),
+    };
+
+    finish_conversion(module, target_lang, stage, BoundsCheckConfig::default(), entry_point, SpvOptions::default())
+}
+
+/// Sibling of `convert_shader` for binary source formats.
+/// source_lang: "spv"
+/// target_lang: "glsl" | "hlsl" | "wgsl" | "msl" | "spv"
+/// stage_str: "vertex" | "fragment" | "compute"
+#[wasm_bindgen]
+pub fn convert_shader_binary(code: &[u8], source_lang: &str, target_lang: &str, stage_str: &str) -> ConversionOutput {
+    let stage = parse_stage(stage_str);
+
+    let module = match source_lang {
+        "spv" => parse_spv(code),
+        _ => Err(format!("Unsupported binary source format: {}", source_lang)),
+    };
+
+    finish_conversion(module, target_lang, stage, BoundsCheckConfig::default(), "", SpvOptions::default())
+}
+
+/// Bundles `convert_shader_opts`'s optional knobs into one `#[wasm_bindgen]` struct instead of
+/// bare positional parameters, since that function had accumulated one per feature (defines,
+/// entry point, bounds-check policies, SPIR-V version/capabilities) and tripped
+/// `clippy::too_many_arguments`.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct ConvertOptions {
+    defines_json: String,
+    entry_point: String,
+    bounds_check: BoundsCheckConfig,
+    spv_options: SpvOptions,
+}
+
+#[wasm_bindgen]
+impl ConvertOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(defines_json: &str, entry_point: &str, bounds_check: BoundsCheckConfig, spv_options: SpvOptions) -> Self {
+        ConvertOptions {
+            defines_json: defines_json.to_string(),
+            entry_point: entry_point.to_string(),
+            bounds_check,
+            spv_options,
+        }
+    }
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            defines_json: String::new(),
+            entry_point: String::new(),
+            bounds_check: BoundsCheckConfig::default(),
+            spv_options: SpvOptions::default(),
+        }
+    }
+}
+
+/// Sibling of `convert_shader` that lets callers pick bounds-check policies for
+/// indexing, buffer access, and texture sampling instead of getting naga's `Unchecked` defaults,
+/// and SPIR-V output version/capabilities instead of the `(1, 3)`/unrestricted defaults of
+/// `SpvOptions::default()`.
+/// Note: the hlsl target can't enforce any bounds-check policy; requesting a non-`Unchecked`
+/// `index`, `buffer`, or `texture` policy with target_lang "hlsl" fails rather than silently
+/// ignoring it. The wgsl writer takes no bounds-check options, so the policies have no effect
+/// on wgsl output.
+#[wasm_bindgen]
+pub fn convert_shader_opts(code: &str, source_lang: &str, target_lang: &str, stage_str: &str, options: ConvertOptions) -> ConversionOutput {
+    let stage = parse_stage(stage_str);
+    let defines = match preprocessor::parse_defines(&options.defines_json) {
+        Ok(d) => d,
+        Err(e) => return error_output(&e),
     };
 
+    let module = match source_lang {
+        "wgsl" => parse_wgsl(code, &defines),
+        "spv" => Err("SPIR-V source is binary; use convert_shader_binary instead".to_string()),
+        "glsl" | _ => parse_glsl(code, stage, &defines),
+    };
+
+    finish_conversion(module, target_lang, stage, options.bounds_check, &options.entry_point, options.spv_options)
+}
+
+#[derive(serde::Deserialize)]
+struct BatchJob {
+    code: String,
+    source_lang: String,
+    target_lang: String,
+    stage: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BatchResult {
+    success: bool,
+    output: String,
+    error: String,
+}
+
+/// Batch sibling of `convert_shader`: parses a JSON array of `{code, source_lang, target_lang,
+/// stage}` jobs and returns a JSON array of `{success, output, error}` results, reusing a single
+/// `Validator` across all jobs instead of allocating one per shader, since that's where
+/// naga-cli's `--bulk-validate` gets its speedup over one-off conversions.
+#[wasm_bindgen]
+pub fn convert_batch(inputs_json: &str) -> String {
+    let jobs: Vec<BatchJob> = match serde_json::from_str(inputs_json) {
+        Ok(jobs) => jobs,
+        Err(e) => return batch_error(&format!("Invalid batch JSON: {:?}", e)),
+    };
+
+    let mut validator = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all());
+
+    let results: Vec<BatchResult> = jobs.iter().map(|job| {
+        let stage = parse_stage(&job.stage);
+        let module = match job.source_lang.as_str() {
+            "wgsl" => parse_wgsl(&job.code, &Defines::new()),
+            "spv" => Err("SPIR-V source is binary; convert_batch only supports text sources".to_string()),
+            "glsl" | _ => parse_glsl(&job.code, stage, &Defines::new()),
+        };
+
+        match finish_conversion_text(module, &job.target_lang, stage, &mut validator) {
+            Ok(output) => BatchResult { success: true, output, error: String::new() },
+            Err(e) => BatchResult { success: false, output: String::new(), error: e },
+        }
+    }).collect();
+
+    serde_json::to_string(&results).unwrap_or_else(|e| batch_error(&format!("{:?}", e)))
+}
+
+fn batch_error(msg: &str) -> String {
+    let result = BatchResult { success: false, output: String::new(), error: msg.to_string() };
+    serde_json::to_string(&[result]).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Validate-and-write tail shared by `convert_batch`, taking a reusable `Validator` instead of
+/// constructing one per call like `finish_conversion` does. Binary targets (e.g. "spv") aren't
+/// supported since `BatchResult.output` is a JSON string field.
+fn finish_conversion_text(module: Result<naga::Module, String>, target_lang: &str, stage: naga::ShaderStage, validator: &mut valid::Validator) -> Result<String, String> {
+    let module = module?;
+    let info = validator.validate(&module).map_err(|e| format!("Validation Error: {:?}", e))?;
+
+    match target_lang {
+        "hlsl" => write_hlsl(&module, &info, BoundsCheckConfig::default()),
+        "wgsl" => write_wgsl(&module, &info),
+        "msl" => write_msl(&module, &info, BoundsCheckConfig::default()),
+        "glsl" => write_glsl(&module, &info, stage, BoundsCheckConfig::default(), ""),
+        _ => Err(format!("Unsupported batch target format: {}", target_lang)),
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EntryPointReflection {
+    name: String,
+    stage: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ResourceBindingReflection {
+    group: u32,
+    binding: u32,
+    name: String,
+    ty: String,
+    access: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ShaderReflection {
+    entry_points: Vec<EntryPointReflection>,
+    resources: Vec<ResourceBindingReflection>,
+}
+
+/// Parses and validates a shader, then returns JSON describing each entry point's name and
+/// stage plus its resource bindings, so callers can build pipeline layouts and pick the right
+/// `entry_point` for `convert_shader` before converting (note that `entry_point` only has an
+/// effect on glsl targets; hlsl and msl always emit every entry point).
+/// source_lang: "glsl" | "wgsl"
+#[wasm_bindgen]
+pub fn reflect_shader(code: &str, source_lang: &str, stage_str: &str) -> String {
+    let stage = parse_stage(stage_str);
+
+    let module = match source_lang {
+        "wgsl" => parse_wgsl(code, &Defines::new()),
+        "glsl" | _ => parse_glsl(code, stage, &Defines::new()),
+    };
+    let module = match module {
+        Ok(m) => m,
+        Err(e) => return reflection_error(&e),
+    };
+
+    let mut validator = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all());
+    if let Err(e) = validator.validate(&module) {
+        return reflection_error(&format!("Validation Error: {:?}", e));
+    }
+
+    let entry_points = module.entry_points.iter().map(|ep| EntryPointReflection {
+        name: ep.name.clone(),
+        stage: format!("{:?}", ep.stage).to_lowercase(),
+    }).collect();
+
+    let resources = module.global_variables.iter().filter_map(|(_, var)| {
+        let binding = var.binding.as_ref()?;
+        Some(ResourceBindingReflection {
+            group: binding.group,
+            binding: binding.binding,
+            name: var.name.clone().unwrap_or_default(),
+            ty: module.types[var.ty].name.clone().unwrap_or_default(),
+            access: format!("{:?}", var.space),
+        })
+    }).collect();
+
+    serde_json::to_string(&ShaderReflection { entry_points, resources })
+        .unwrap_or_else(|e| reflection_error(&format!("{:?}", e)))
+}
+
+fn reflection_error(msg: &str) -> String {
+    format!("{{\"error\":{:?}}}", msg)
+}
+
+pub(crate) fn parse_stage(stage_str: &str) -> naga::ShaderStage {
+    match stage_str {
+        "vertex" => naga::ShaderStage::Vertex,
+        "compute" => naga::ShaderStage::Compute,
+        _ => naga::ShaderStage::Fragment,
+    }
+}
+
+/// Shared tail of the pipeline: validate a parsed module and write it to the target format.
+/// `entry_point` selects which entry point to target for backends that pick a single one
+/// (glsl only; hlsl and msl always emit every entry point); pass "" to default to "main".
+pub(crate) fn finish_conversion(module: Result<naga::Module, String>, target_lang: &str, stage: naga::ShaderStage, bounds_check: BoundsCheckConfig, entry_point: &str, spv_options: SpvOptions) -> ConversionOutput {
     let module = match module {
         Ok(m) => m,
         Err(e) => return error_output(&e),
@@ -57,17 +437,25 @@ pub fn convert_shader(code: &str, source_lang: &str, target_lang: &str, stage_st
 
     // Write to target format
     let result_code = match target_lang {
-        "hlsl" => write_hlsl(&module, &info),
-        "wgsl" => write_wgsl(&module, &info),
-        "msl" => write_msl(&module, &info),
-        "glsl" => write_glsl(&module, &info, stage),
+        "hlsl" => write_hlsl(&module, &info, bounds_check).map(ConversionPayload::Text),
+        "wgsl" => write_wgsl(&module, &info).map(ConversionPayload::Text),
+        "msl" => write_msl(&module, &info, bounds_check).map(ConversionPayload::Text),
+        "glsl" => write_glsl(&module, &info, stage, bounds_check, entry_point).map(ConversionPayload::Text),
+        "spv" => write_spv(&module, &info, bounds_check, &spv_options).map(ConversionPayload::Binary),
         _ => Err(format!("Unknown target format: {}", target_lang)),
     };
 
     match result_code {
-        Ok(output) => ConversionOutput {
+        Ok(ConversionPayload::Text(output)) => ConversionOutput {
             success: true,
             output,
+            output_bytes: Vec::new(),
+            error: String::new(),
+        },
+        Ok(ConversionPayload::Binary(bytes)) => ConversionOutput {
+            success: true,
+            output: String::new(),
+            output_bytes: bytes,
             error: String::new(),
         },
         Err(e) => error_output(&e),
@@ -77,12 +465,14 @@ pub fn convert_shader(code: &str, source_lang: &str, target_lang: &str, stage_st
 /// Legacy function for backwards compatibility
 #[wasm_bindgen]
 pub fn convert_glsl(code: &str, format: &str, stage_str: &str) -> ConversionOutput {
-    convert_shader(code, "glsl", format, stage_str)
+    convert_shader(code, "glsl", format, stage_str, "", "")
 }
 
-fn parse_glsl(code: &str, stage: naga::ShaderStage) -> Result<naga::Module, String> {
+pub(crate) fn parse_glsl(code: &str, stage: naga::ShaderStage, defines: &Defines) -> Result<naga::Module, String> {
+    let preprocessed = preprocessor::preprocess(code, defines)?;
+
     // Preprocessing for Naga/Vulkan Compatibility
-    let mut clean_code = code.lines()
+    let mut clean_code = preprocessed.lines()
         .filter(|l| !l.starts_with("#version"))
         .filter(|l| !l.starts_with("precision"))
         .collect::<Vec<&str>>()
@@ -108,27 +498,52 @@ layout(std140, set=0, binding=0) uniform Globals {{
     let mut parser = front::glsl::Frontend::default();
     let options = front::glsl::Options {
         stage,
-        defines: Default::default(),
+        defines: defines.iter().map(|(k, v)| (k.clone(), v.as_str())).collect(),
     };
-    
+
     parser.parse(&options, &refined_code)
         .map_err(|e| format!("GLSL Parse Error: {:?}\n\nPreprocessed Code:\n{}", e, refined_code))
 }
 
-fn parse_wgsl(code: &str) -> Result<naga::Module, String> {
-    front::wgsl::parse_str(code)
+pub(crate) fn parse_wgsl(code: &str, defines: &Defines) -> Result<naga::Module, String> {
+    let preprocessed = preprocessor::preprocess(code, defines)?;
+    front::wgsl::parse_str(&preprocessed)
         .map_err(|e| format!("WGSL Parse Error: {:?}", e))
 }
 
-fn error_output(msg: &str) -> ConversionOutput {
+fn parse_spv(bytes: &[u8]) -> Result<naga::Module, String> {
+    let options = front::spv::Options {
+        adjust_coordinate_space: false,
+        strict_capabilities: false,
+        block_ctx_dump_prefix: None,
+    };
+    let mut frontend = front::spv::Frontend::new(
+        bytes.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+        &options,
+    );
+    frontend.parse().map_err(|e| format!("SPIR-V Parse Error: {:?}", e))
+}
+
+pub(crate) fn error_output(msg: &str) -> ConversionOutput {
     ConversionOutput {
         success: false,
         output: String::new(),
+        output_bytes: Vec::new(),
         error: msg.to_string(),
     }
 }
 
-fn write_hlsl(module: &naga::Module, info: &valid::ModuleInfo) -> Result<String, String> {
+/// naga's HLSL backend (`back::hlsl::Options`) has no bounds-check-related field at all in this
+/// naga version, so none of the `index`/`buffer`/`texture` policies can be enforced for this
+/// target; any non-`Unchecked` policy is rejected rather than silently ignored.
+fn write_hlsl(module: &naga::Module, info: &valid::ModuleInfo, bounds_check: BoundsCheckConfig) -> Result<String, String> {
+    if !matches!(bounds_check.index, BoundsCheckPolicy::Unchecked)
+        || !matches!(bounds_check.buffer, BoundsCheckPolicy::Unchecked)
+        || !matches!(bounds_check.texture, BoundsCheckPolicy::Unchecked)
+    {
+        return Err("HLSL backend has no bounds-check configuration in this naga version; `index`/`buffer`/`texture` policies can't be enforced for this target".to_string());
+    }
+
     let mut string = String::new();
     let options = back::hlsl::Options::default();
     let mut writer = back::hlsl::Writer::new(&mut string, &options);
@@ -141,16 +556,20 @@ fn write_wgsl(module: &naga::Module, info: &valid::ModuleInfo) -> Result<String,
     back::wgsl::write_string(module, info, options).map_err(|e| format!("{:?}", e))
 }
 
-fn write_msl(module: &naga::Module, info: &valid::ModuleInfo) -> Result<String, String> {
+/// Unlike GLSL, naga's MSL `PipelineOptions` only carries pipeline-wide flags (vertex pulling,
+/// point size) and has no field to select a single entry point, so MSL emits every entry point
+/// into one text blob, same as `write_hlsl`.
+fn write_msl(module: &naga::Module, info: &valid::ModuleInfo, bounds_check: BoundsCheckConfig) -> Result<String, String> {
     let mut string = String::new();
-    let options = back::msl::Options::default();
-    let binding_map = back::msl::PipelineOptions::default();
+    let mut options = back::msl::Options::default();
+    options.bounds_check_policies = bounds_check.to_naga_policies();
+    let pipeline_options = back::msl::PipelineOptions::default();
     let mut writer = back::msl::Writer::new(&mut string);
-    writer.write(module, info, &options, &binding_map).map_err(|e| format!("{:?}", e))?;
+    writer.write(module, info, &options, &pipeline_options).map_err(|e| format!("{:?}", e))?;
     Ok(string)
 }
 
-fn write_glsl(module: &naga::Module, info: &valid::ModuleInfo, stage: naga::ShaderStage) -> Result<String, String> {
+fn write_glsl(module: &naga::Module, info: &valid::ModuleInfo, stage: naga::ShaderStage, bounds_check: BoundsCheckConfig, entry_point: &str) -> Result<String, String> {
     let mut string = String::new();
     let options = back::glsl::Options {
         version: back::glsl::Version::Desktop(450),
@@ -160,15 +579,39 @@ fn write_glsl(module: &naga::Module, info: &valid::ModuleInfo, stage: naga::Shad
     };
     let pipeline_options = back::glsl::PipelineOptions {
         shader_stage: stage,
-        entry_point: "main".to_string(),
+        entry_point: if entry_point.is_empty() { "main".to_string() } else { entry_point.to_string() },
         multiview: None,
     };
-    let mut writer = back::glsl::Writer::new(&mut string, module, info, &options, &pipeline_options, Default::default())
+    let policies = bounds_check.to_naga_policies();
+    let mut writer = back::glsl::Writer::new(&mut string, module, info, &options, &pipeline_options, policies)
         .map_err(|e| format!("{:?}", e))?;
     writer.write().map_err(|e| format!("{:?}", e))?;
     Ok(string)
 }
 
+/// Writes a module to SPIR-V, flattening the `u32` words into little-endian bytes
+/// since `ConversionOutput.output` is a `String` and can't hold raw binary
+fn write_spv(module: &naga::Module, info: &valid::ModuleInfo, bounds_check: BoundsCheckConfig, spv_options: &SpvOptions) -> Result<Vec<u8>, String> {
+    let options = back::spv::Options {
+        lang_version: spv_options.lang_version(),
+        flags: back::spv::WriterFlags::empty(),
+        capabilities: spv_options.capabilities()?,
+        bounds_check_policies: bounds_check.to_naga_policies(),
+        binding_map: Default::default(),
+        zero_initialize_workgroup_memory: back::spv::ZeroInitializeWorkgroupMemoryMode::Polyfill,
+        debug_info: None,
+    };
+    let mut writer = back::spv::Writer::new(&options).map_err(|e| format!("{:?}", e))?;
+    let mut words = Vec::new();
+    writer.write(module, info, None, &None, &mut words).map_err(|e| format!("{:?}", e))?;
+
+    let mut bytes = Vec::with_capacity(words.len() * 4);
+    for word in words {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,14 +619,116 @@ mod tests {
     #[test]
     fn test_glsl_to_wgsl() {
         let glsl = "void main() { gl_FragColor = vec4(1.0); }";
-        let result = convert_shader(glsl, "glsl", "wgsl", "fragment");
+        let result = convert_shader(glsl, "glsl", "wgsl", "fragment", "", "");
         assert!(result.success);
     }
 
     #[test]
     fn test_wgsl_to_hlsl() {
         let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
-        let result = convert_shader(wgsl, "wgsl", "hlsl", "fragment");
+        let result = convert_shader(wgsl, "wgsl", "hlsl", "fragment", "", "");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_shader_opts_hlsl_rejects_bounds_check_policy() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let bounds_check = BoundsCheckConfig::new(
+            BoundsCheckPolicy::Restrict,
+            BoundsCheckPolicy::Unchecked,
+            BoundsCheckPolicy::Unchecked,
+        );
+        let options = ConvertOptions::new("", "", bounds_check, SpvOptions::default());
+        let result = convert_shader_opts(wgsl, "wgsl", "hlsl", "fragment", options);
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_wgsl_to_spv() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let result = convert_shader(wgsl, "wgsl", "spv", "fragment", "", "");
+        assert!(result.success);
+        assert!(!result.output_binary().is_empty());
+    }
+
+    #[test]
+    fn test_convert_shader_opts_custom_spv_version() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let spv_options = SpvOptions::new(1, 5, r#"["Shader"]"#);
+        let options = ConvertOptions::new("", "", BoundsCheckConfig::default(), spv_options);
+        let result = convert_shader_opts(wgsl, "wgsl", "spv", "fragment", options);
+        assert!(result.success);
+        assert!(!result.output_binary().is_empty());
+    }
+
+    #[test]
+    fn test_convert_shader_opts_rejects_unknown_spv_capability() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let spv_options = SpvOptions::new(1, 3, r#"["NotARealCapability"]"#);
+        let options = ConvertOptions::new("", "", BoundsCheckConfig::default(), spv_options);
+        let result = convert_shader_opts(wgsl, "wgsl", "spv", "fragment", options);
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_spv_roundtrip_to_wgsl() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let spv = convert_shader(wgsl, "wgsl", "spv", "fragment", "", "");
+        assert!(spv.success);
+
+        let result = convert_shader_binary(&spv.output_binary(), "spv", "wgsl", "fragment");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_shader_opts_restrict_policy() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let bounds_check = BoundsCheckConfig::new(
+            BoundsCheckPolicy::Restrict,
+            BoundsCheckPolicy::Restrict,
+            BoundsCheckPolicy::ReadZeroSkipWrite,
+        );
+        let options = ConvertOptions::new("", "", bounds_check, SpvOptions::default());
+        let result = convert_shader_opts(wgsl, "wgsl", "msl", "fragment", options);
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_shader_with_ifdef_define() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> {\n#ifdef USE_RED\n    return vec4<f32>(1.0, 0.0, 0.0, 1.0);\n#else\n    return vec4<f32>(0.0);\n#endif\n}";
+        let result = convert_shader(wgsl, "wgsl", "wgsl", "fragment", r#"{"USE_RED": true}"#, "");
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_convert_batch() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let inputs = format!(
+            r#"[{{"code":{:?},"source_lang":"wgsl","target_lang":"hlsl","stage":"fragment"}},
+                {{"code":"not a shader","source_lang":"wgsl","target_lang":"hlsl","stage":"fragment"}}]"#,
+            wgsl
+        );
+
+        let output = convert_batch(&inputs);
+        let results: Vec<BatchResult> = serde_json::from_str(&output).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].success);
+        assert!(!results[1].success);
+    }
+
+    #[test]
+    fn test_convert_shader_with_named_entry_point() {
+        let wgsl = "@fragment fn tonemap() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let result = convert_shader(wgsl, "wgsl", "glsl", "fragment", "", "tonemap");
         assert!(result.success);
     }
+
+    #[test]
+    fn test_reflect_shader() {
+        let wgsl = "@fragment fn main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        let json = reflect_shader(wgsl, "wgsl", "fragment");
+        let reflection: ShaderReflection = serde_json::from_str(&json).unwrap();
+        assert_eq!(reflection.entry_points.len(), 1);
+        assert_eq!(reflection.entry_points[0].name, "main");
+    }
 }
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+/// A preprocessor `#define` value, mirroring naga_oil's `ShaderDefValue`
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShaderDefValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl ShaderDefValue {
+    fn is_truthy(&self) -> bool {
+        match self {
+            ShaderDefValue::Bool(b) => *b,
+            ShaderDefValue::Int(i) => *i != 0,
+            ShaderDefValue::String(s) => !s.is_empty(),
+        }
+    }
+
+    pub fn as_str(&self) -> String {
+        match self {
+            ShaderDefValue::Bool(b) => b.to_string(),
+            ShaderDefValue::Int(i) => i.to_string(),
+            ShaderDefValue::String(s) => s.clone(),
+        }
+    }
+}
+
+pub type Defines = HashMap<String, ShaderDefValue>;
+
+/// Parses the `{name: bool|integer|string}` defines object passed from JS as a JSON string
+pub fn parse_defines(json: &str) -> Result<Defines, String> {
+    if json.trim().is_empty() {
+        return Ok(Defines::new());
+    }
+
+    let raw: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| format!("Invalid defines JSON: {:?}", e))?;
+    let object = raw.as_object().ok_or_else(|| "defines JSON must be an object".to_string())?;
+
+    let mut defines = Defines::new();
+    for (key, value) in object {
+        let def = match value {
+            serde_json::Value::Bool(b) => ShaderDefValue::Bool(*b),
+            serde_json::Value::Number(n) if n.is_i64() => ShaderDefValue::Int(n.as_i64().unwrap()),
+            serde_json::Value::String(s) => ShaderDefValue::String(s.clone()),
+            other => return Err(format!("Unsupported define value for `{}`: {:?}", key, other)),
+        };
+        defines.insert(key.clone(), def);
+    }
+    Ok(defines)
+}
+
+/// Evaluates `#ifdef`/`#ifndef`/`#if NAME == value`/`#else`/`#endif` blocks and substitutes
+/// `#NAME` tokens, stripping disabled regions. Used ahead of both the GLSL and WGSL frontends;
+/// WGSL has no native preprocessor, and for GLSL `parse_glsl` also hands the defines on to the
+/// naga GLSL frontend's own preprocessor.
+pub fn preprocess(source: &str, defines: &Defines) -> Result<String, String> {
+    let mut output = Vec::new();
+    // Stack of (branch currently active, this #if/#else has already taken a branch)
+    let mut stack: Vec<(bool, bool)> = Vec::new();
+
+    for (line_no, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let parent_active = stack.iter().all(|(active, _)| *active);
+
+        if let Some(rest) = trimmed.strip_prefix("#ifdef") {
+            let cond = defines.contains_key(rest.trim());
+            stack.push((parent_active && cond, cond));
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("#ifndef") {
+            let cond = !defines.contains_key(rest.trim());
+            stack.push((parent_active && cond, cond));
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("#if") {
+            let cond = eval_if_condition(rest.trim(), defines)
+                .map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+            stack.push((parent_active && cond, cond));
+            continue;
+        }
+        if trimmed.starts_with("#else") {
+            let (_, taken) = stack.pop()
+                .ok_or_else(|| format!("line {}: #else without matching #if", line_no + 1))?;
+            let grandparent_active = stack.iter().all(|(active, _)| *active);
+            stack.push((grandparent_active && !taken, true));
+            continue;
+        }
+        if trimmed.starts_with("#endif") {
+            stack.pop().ok_or_else(|| format!("line {}: #endif without matching #if", line_no + 1))?;
+            continue;
+        }
+
+        if parent_active {
+            output.push(substitute_defines(line, defines));
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(format!("unterminated #if/#ifdef block ({} still open)", stack.len()));
+    }
+
+    Ok(output.join("\n"))
+}
+
+fn eval_if_condition(expr: &str, defines: &Defines) -> Result<bool, String> {
+    if let Some((name, value)) = expr.split_once("==") {
+        let actual = defines.get(name.trim()).map(ShaderDefValue::as_str).unwrap_or_default();
+        return Ok(actual == value.trim());
+    }
+    Ok(defines.get(expr).map(ShaderDefValue::is_truthy).unwrap_or(false))
+}
+
+fn substitute_defines(line: &str, defines: &Defines) -> String {
+    let mut result = line.to_string();
+    for (name, value) in defines {
+        let token = format!("#{}", name);
+        result = replace_word(&result, &token, &value.as_str());
+    }
+    result
+}
+
+/// Replaces whole-word occurrences of `word` with `replacement`, leaving it alone when it's a
+/// prefix/suffix of a longer identifier (e.g. replacing `#MAX` must not corrupt `#MAX_LIGHTS`).
+pub(crate) fn replace_word(source: &str, word: &str, replacement: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(idx) = rest.find(word) {
+        let before_ok = idx == 0 || !is_ident_char(rest.as_bytes()[idx - 1] as char);
+        let after_idx = idx + word.len();
+        let after_ok = after_idx >= rest.len() || !is_ident_char(rest.as_bytes()[after_idx] as char);
+
+        result.push_str(&rest[..idx]);
+        result.push_str(if before_ok && after_ok { replacement } else { word });
+        rest = &rest[after_idx..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ifdef_strips_disabled_branch() {
+        let mut defines = Defines::new();
+        defines.insert("USE_FOG".to_string(), ShaderDefValue::Bool(true));
+
+        let source = "a\n#ifdef USE_FOG\nb\n#else\nc\n#endif\nd";
+        let result = preprocess(source, &defines).unwrap();
+        assert_eq!(result, "a\nb\nd");
+    }
+
+    #[test]
+    fn test_token_substitution() {
+        let mut defines = Defines::new();
+        defines.insert("MAX_LIGHTS".to_string(), ShaderDefValue::Int(4));
+
+        let result = preprocess("const int n = #MAX_LIGHTS;", &defines).unwrap();
+        assert_eq!(result, "const int n = 4;");
+    }
+
+    #[test]
+    fn test_token_substitution_does_not_clobber_prefix_sharing_define() {
+        let mut defines = Defines::new();
+        defines.insert("MAX".to_string(), ShaderDefValue::Int(8));
+        defines.insert("MAX_LIGHTS".to_string(), ShaderDefValue::Int(4));
+
+        let result = preprocess("const int a = #MAX; const int b = #MAX_LIGHTS;", &defines).unwrap();
+        assert_eq!(result, "const int a = 8; const int b = 4;");
+    }
+}